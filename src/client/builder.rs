@@ -0,0 +1,203 @@
+#![allow(missing_debug_implementations)]
+//! Builds a [`Client`], applying the TLS and HTTP/2 fingerprinting options
+//! defined in [`crate::tls::extension`] and [`crate::util::client`].
+
+use boring::ssl::{ConnectConfiguration, SslConnector, SslConnectorBuilder, SslMethod};
+use bytes::Bytes;
+use http::HeaderMap;
+use http_body::Body;
+
+use crate::error::BoxError;
+use crate::tls::extension::{EchSetting, Identity, Pin, TlsExtension};
+use crate::util::client::{append_accept_encoding, ContentEncoding, Decoder, Http2Settings};
+use crate::HttpVersionPref;
+
+/// Builds a [`Client`], configuring the TLS and HTTP/2 behavior used to
+/// reproduce a given impersonation profile's fingerprint.
+#[derive(Default)]
+pub struct ClientBuilder {
+    http_version_pref: HttpVersionPref,
+    http2_settings: Option<Http2Settings>,
+    identity: Option<Identity>,
+    ech_setting: Option<EchSetting>,
+    cert_pinning: Vec<Pin>,
+    accept_encodings: Vec<ContentEncoding>,
+}
+
+impl ClientBuilder {
+    /// Creates a new, unconfigured builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The HTTP version(s) this client should negotiate.
+    pub fn http_version_pref(mut self, http_version_pref: HttpVersionPref) -> Self {
+        self.http_version_pref = http_version_pref;
+        self
+    }
+
+    /// Set the HTTP/2 settings used on connections from this client.
+    ///
+    /// Only the SETTINGS values are applied to the h2 connection builder (see
+    /// [`Http2Settings::apply`]); the settings order, WINDOW_UPDATE increment,
+    /// PRIORITY frames and pseudo-header order are stored but not applied.
+    pub fn http2_settings(mut self, http2_settings: Http2Settings) -> Self {
+        self.http2_settings = Some(http2_settings);
+        self
+    }
+
+    /// Present a client certificate identity for mutual TLS.
+    pub fn identity(mut self, identity: Identity) -> Self {
+        self.identity = Some(identity);
+        self
+    }
+
+    /// Send ECH GREASE (decoy extensions) without performing real Encrypted
+    /// Client Hello.
+    pub fn ech_grease(mut self) -> Self {
+        self.ech_setting = Some(EchSetting::Grease);
+        self
+    }
+
+    /// Perform genuine Encrypted Client Hello using the given ECHConfigList.
+    pub fn ech_config_list(mut self, config_list: Vec<u8>) -> Self {
+        self.ech_setting = Some(EchSetting::ConfigList(config_list));
+        self
+    }
+
+    /// Pin the server certificate's public keys, enforced in addition to
+    /// normal chain verification. An empty `pins` is a no-op.
+    pub fn cert_pinning(mut self, pins: Vec<Pin>) -> Self {
+        self.cert_pinning = pins;
+        self
+    }
+
+    /// Enable (or disable) transparent gzip response decompression,
+    /// advertising it in the `Accept-Encoding` request header.
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.set_accept_encoding(ContentEncoding::Gzip, enable);
+        self
+    }
+
+    /// Enable (or disable) transparent deflate response decompression,
+    /// advertising it in the `Accept-Encoding` request header.
+    pub fn deflate(mut self, enable: bool) -> Self {
+        self.set_accept_encoding(ContentEncoding::Deflate, enable);
+        self
+    }
+
+    /// Enable (or disable) transparent brotli response decompression,
+    /// advertising it in the `Accept-Encoding` request header.
+    pub fn brotli(mut self, enable: bool) -> Self {
+        self.set_accept_encoding(ContentEncoding::Brotli, enable);
+        self
+    }
+
+    /// Enable (or disable) transparent zstd response decompression,
+    /// advertising it in the `Accept-Encoding` request header.
+    pub fn zstd(mut self, enable: bool) -> Self {
+        self.set_accept_encoding(ContentEncoding::Zstd, enable);
+        self
+    }
+
+    fn set_accept_encoding(&mut self, encoding: ContentEncoding, enable: bool) {
+        self.accept_encodings
+            .retain(|existing| *existing != encoding);
+        if enable {
+            self.accept_encodings.push(encoding);
+        }
+    }
+
+    /// Builds the `Client`, applying every configured TLS and HTTP/2 option.
+    pub fn build(self) -> Result<Client, BoxError> {
+        let connector = SslConnector::builder(SslMethod::tls())?;
+        let connector = connector.configure_alpn_protos(self.http_version_pref)?;
+
+        let connector = connector.configure_cert_pinning(self.cert_pinning)?;
+
+        let connector = match self.identity {
+            Some(identity) => connector.configure_identity(identity)?,
+            None => connector,
+        };
+
+        let mut h2_builder = h2::client::Builder::new();
+        if let Some(http2_settings) = &self.http2_settings {
+            http2_settings.apply(&mut h2_builder);
+        }
+
+        Ok(Client {
+            connector,
+            http_version_pref: self.http_version_pref,
+            h2_builder,
+            ech_setting: self.ech_setting,
+            accept_encodings: self.accept_encodings,
+        })
+    }
+}
+
+/// A client configured to reproduce a given impersonation profile's TLS and
+/// HTTP/2 fingerprint.
+#[allow(missing_debug_implementations)]
+pub struct Client {
+    #[allow(dead_code)]
+    connector: SslConnectorBuilder,
+    http_version_pref: HttpVersionPref,
+    h2_builder: h2::client::Builder,
+    ech_setting: Option<EchSetting>,
+    accept_encodings: Vec<ContentEncoding>,
+}
+
+impl Client {
+    /// Starts building a `Client`.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    /// The HTTP version(s) this client negotiates.
+    pub fn http_version_pref(&self) -> HttpVersionPref {
+        self.http_version_pref
+    }
+
+    /// The h2 connection builder configured with this client's
+    /// [`Http2Settings`].
+    pub(crate) fn h2_builder(&self) -> &h2::client::Builder {
+        &self.h2_builder
+    }
+
+    /// Applies this client's ECH setting (GREASE or a real config list), if
+    /// any, to a connection in progress.
+    pub(crate) fn configure_ech(
+        &self,
+        connect_configuration: &mut ConnectConfiguration,
+    ) -> Result<(), BoxError> {
+        if let Some(ech_setting) = &self.ech_setting {
+            ech_setting.configure(connect_configuration)?;
+        }
+        Ok(())
+    }
+
+    /// Appends this client's configured encodings to a request's
+    /// `Accept-Encoding` header.
+    pub(crate) fn negotiate_accept_encoding(&self, headers: &mut HeaderMap) {
+        append_accept_encoding(headers, &self.accept_encodings);
+    }
+
+    /// Wraps a response body in a [`Decoder`] for whatever `Content-Encoding`
+    /// its headers name, stripping the headers that no longer describe the
+    /// decoded body.
+    pub(crate) fn decode_response_body<B>(&self, headers: &mut HeaderMap, body: B) -> Decoder<B>
+    where
+        B: Body<Data = Bytes> + Unpin,
+        B::Error: Into<BoxError>,
+    {
+        let encoding = headers
+            .get(http::header::CONTENT_ENCODING)
+            .and_then(ContentEncoding::from_header_value);
+
+        if encoding.is_some() {
+            Decoder::<B>::strip_headers(headers);
+        }
+
+        Decoder::new(body, encoding)
+    }
+}
@@ -0,0 +1,22 @@
+//! HTTP version negotiation preferences.
+
+/// Which HTTP version(s) a request (or connector) should negotiate.
+///
+/// This drives the ALPN protocols offered on the TLS handshake: `Http1` and
+/// `Http2` (and `All`, their TCP-negotiated combination) all run over a
+/// single TCP connection. `Http3` is recorded as a preference only: no QUIC
+/// connector is provided, and the TCP/TLS connector offers the same ALPN
+/// protocols for it as for `All`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HttpVersionPref {
+    /// HTTP/1.1 only.
+    Http1,
+    /// HTTP/2 only, over TCP.
+    #[default]
+    Http2,
+    /// HTTP/3. Negotiated over TCP/TLS the same way as `All`; no QUIC
+    /// transport is provided.
+    Http3,
+    /// HTTP/2 and HTTP/1.1, negotiated over TCP via ALPN.
+    All,
+}
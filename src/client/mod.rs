@@ -0,0 +1,8 @@
+//! Client construction: version preferences and the builder entry points for
+//! the TLS/HTTP impersonation options in [`crate::tls`].
+
+pub mod builder;
+pub mod http;
+
+pub use builder::{Client, ClientBuilder};
+pub use http::HttpVersionPref;
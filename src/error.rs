@@ -0,0 +1,5 @@
+//! Shared error types.
+
+/// A type-erased error, used wherever a fallible operation doesn't need a
+/// dedicated error type of its own.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
@@ -0,0 +1,9 @@
+//! `rquest`: an impersonating HTTP client built on top of `boring` and `h2`.
+
+pub mod client;
+pub mod dns;
+pub mod error;
+pub mod tls;
+pub(crate) mod util;
+
+pub use client::{http::HttpVersionPref, Client, ClientBuilder};
@@ -1,10 +1,16 @@
 #![allow(missing_debug_implementations)]
 use super::{cert_compression::CertCompressionAlgorithm, TlsResult, Version};
 use crate::client::http::HttpVersionPref;
+use crate::error::BoxError;
 use ::std::os::raw::c_int;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use boring::error::ErrorStack;
-use boring::ssl::{ConnectConfiguration, SslConnectorBuilder, SslVerifyMode, SslVersion};
+use boring::hash::{hash, MessageDigest};
+use boring::pkcs12::Pkcs12;
+use boring::pkey::{PKey, Private};
+use boring::ssl::{ConnectConfiguration, SslConnectorBuilder, SslRef, SslVerifyMode, SslVersion};
 use boring::x509::store::X509Store;
+use boring::x509::{X509Ref, X509};
 use foreign_types::ForeignTypeRef;
 
 /// Error handler for the boringssl functions.
@@ -16,6 +22,76 @@ fn sv_handler(r: c_int) -> Result<c_int, ErrorStack> {
     }
 }
 
+/// A pinned certificate public key, in the HPKP `sha256//<base64>` format.
+///
+/// The pinned value is the base64-encoded SHA-256 digest of a certificate's
+/// DER-encoded SubjectPublicKeyInfo, as produced by tools like `openssl x509
+/// -pubkey | openssl pkey -pubin -outform der | openssl dgst -sha256 -binary
+/// | openssl enc -base64`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pin(String);
+
+impl Pin {
+    /// Creates a new pin from a `sha256//<base64>` formatted string.
+    pub fn new(pin: impl Into<String>) -> Self {
+        Self(pin.into())
+    }
+}
+
+impl<T> From<T> for Pin
+where
+    T: Into<String>,
+{
+    fn from(pin: T) -> Self {
+        Self::new(pin)
+    }
+}
+
+/// A client certificate identity, used to authenticate to servers that
+/// require mutual TLS.
+///
+/// Built from a password-protected PKCS #12 bundle containing a leaf
+/// certificate, its private key, and (optionally) a chain of intermediate
+/// certificates to present alongside it.
+///
+/// Parse a bundle with [`Identity::from_pkcs12_der`] and hand it to
+/// [`crate::ClientBuilder::identity`], which applies it to the connector via
+/// [`TlsExtension::configure_identity`].
+#[allow(missing_debug_implementations)]
+pub struct Identity {
+    cert: X509,
+    key: PKey<Private>,
+    chain: Vec<X509>,
+}
+
+impl Identity {
+    /// Parses a DER-encoded PKCS #12 archive, decrypting it with the given
+    /// password.
+    pub fn from_pkcs12_der(der: &[u8], password: &str) -> Result<Identity, BoxError> {
+        let pkcs12 = Pkcs12::from_der(der)?.parse2(password)?;
+
+        let cert = pkcs12
+            .cert
+            .ok_or("PKCS#12 bundle does not contain a certificate")?;
+        let key = pkcs12
+            .pkey
+            .ok_or("PKCS#12 bundle does not contain a private key")?;
+        let chain = pkcs12
+            .ca
+            .map(|stack| stack.into_iter().collect())
+            .unwrap_or_default();
+
+        Ok(Identity { cert, key, chain })
+    }
+}
+
+/// Computes the `sha256//<base64>` pin for the given certificate's public key.
+fn spki_pin(cert: &X509Ref) -> Option<String> {
+    let spki_der = cert.public_key().ok()?.public_key_to_der().ok()?;
+    let digest = hash(MessageDigest::sha256(), &spki_der).ok()?;
+    Some(format!("sha256//{}", STANDARD.encode(digest)))
+}
+
 /// TlsExtension trait for `SslConnectorBuilder`.
 pub trait TlsExtension {
     /// Configure the certificate verification for the given `SslConnectorBuilder`.
@@ -59,6 +135,18 @@ pub trait TlsExtension {
         permute_extensions: bool,
     ) -> TlsResult<SslConnectorBuilder>;
 
+    /// Configure public-key (SPKI) pinning for the given `SslConnectorBuilder`.
+    ///
+    /// Pinning is enforced on top of the normal certificate chain validation,
+    /// never in place of it: the handshake is only accepted if the chain both
+    /// passes standard verification *and* contains at least one certificate
+    /// whose SPKI matches one of the given pins. An empty pin set is a no-op.
+    fn configure_cert_pinning(self, pins: Vec<Pin>) -> TlsResult<SslConnectorBuilder>;
+
+    /// Configure a client certificate identity for mutual TLS on the given
+    /// `SslConnectorBuilder`.
+    fn configure_identity(self, identity: Identity) -> TlsResult<SslConnectorBuilder>;
+
     /// Configure the set_verify_cert_store for the given `SslConnectorBuilder`.
     #[cfg(feature = "boring-tls-native-roots")]
     fn configure_set_verify_cert_store(self) -> TlsResult<SslConnectorBuilder>;
@@ -79,6 +167,79 @@ pub trait TlsConnectExtension {
         enable: bool,
         http_version: HttpVersionPref,
     ) -> TlsResult<&mut ConnectConfiguration>;
+
+    /// Install a real ECHConfigList on the given `ConnectConfiguration`, so the
+    /// handshake performs genuine Encrypted Client Hello rather than GREASE.
+    ///
+    /// `config_list` is the HPKE public config fetched from a DNS HTTPS/SVCB
+    /// record (or otherwise supplied out of band). A malformed config list is
+    /// reported as a `TlsResult` error rather than silently falling back to an
+    /// unencrypted ClientHello.
+    fn configure_ech_config_list(
+        &mut self,
+        config_list: &[u8],
+    ) -> TlsResult<&mut ConnectConfiguration>;
+}
+
+/// How an impersonation profile should configure Encrypted Client Hello.
+#[derive(Clone, Debug)]
+pub enum EchSetting {
+    /// Send ECH GREASE (decoy extensions) without performing real ECH.
+    Grease,
+    /// Perform genuine ECH using the given HPKE ECHConfigList.
+    ConfigList(Vec<u8>),
+}
+
+impl EchSetting {
+    /// Applies this setting to the given `ConnectConfiguration`, dispatching
+    /// to GREASE or a real ECHConfigList as appropriate.
+    pub fn configure(
+        &self,
+        connect_configuration: &mut ConnectConfiguration,
+    ) -> TlsResult<()> {
+        match self {
+            EchSetting::Grease => {
+                connect_configuration.configure_enable_ech_grease(true, true)?;
+            }
+            EchSetting::ConfigList(config_list) => {
+                connect_configuration.configure_ech_config_list(config_list)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Retrieves the server's ECH `retry_configs` from a rejected handshake, so
+/// the caller can retry the connection with the up-to-date config.
+///
+/// Only meaningful after a handshake has failed with an ECH rejection (i.e.
+/// `SSL_get_error` reports the handshake failed because the server rejected
+/// the client's ECHConfigList); returns `None` if the server sent no retry
+/// configs.
+pub fn ech_retry_configs(ssl: &SslRef) -> Option<Vec<u8>> {
+    unsafe {
+        let mut ptr: *const u8 = std::ptr::null();
+        let mut len: usize = 0;
+        boring_sys::SSL_get0_ech_retry_configs(ssl.as_ptr(), &mut ptr, &mut len);
+
+        if ptr.is_null() || len == 0 {
+            None
+        } else {
+            Some(std::slice::from_raw_parts(ptr, len).to_vec())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_pkcs12_der_rejects_garbage_bytes() {
+        let err = Identity::from_pkcs12_der(b"not a pkcs12 bundle", "password").unwrap_err();
+        assert!(!err.to_string().is_empty());
+    }
 }
 
 impl TlsExtension for SslConnectorBuilder {
@@ -105,7 +266,12 @@ impl TlsExtension for SslConnectorBuilder {
             HttpVersionPref::Http2 => {
                 self.set_alpn_protos(b"\x02h2")?;
             }
-            HttpVersionPref::All => {
+            // This builder configures the TCP/TLS connector. HTTP/3 is
+            // negotiated over QUIC via Alt-Svc and is never offered in a TCP
+            // ClientHello, so advertising "h3" here would be a lie the
+            // connector can't back up; fall back to the same TCP negotiation
+            // as `All` until a QUIC connector exists to actually speak it.
+            HttpVersionPref::Http3 | HttpVersionPref::All => {
                 self.set_alpn_protos(b"\x02h2\x08http/1.1")?;
             }
         }
@@ -187,6 +353,41 @@ impl TlsExtension for SslConnectorBuilder {
         Ok(self)
     }
 
+    fn configure_cert_pinning(mut self, pins: Vec<Pin>) -> TlsResult<SslConnectorBuilder> {
+        if pins.is_empty() {
+            return Ok(self);
+        }
+
+        self.set_verify_callback(SslVerifyMode::PEER, move |preverify_ok, x509_ctx| {
+            if !preverify_ok {
+                return false;
+            }
+
+            let Some(chain) = x509_ctx.chain() else {
+                return false;
+            };
+
+            chain
+                .iter()
+                .filter_map(spki_pin)
+                .any(|cert_pin| pins.iter().any(|pin| pin.0 == cert_pin))
+        });
+
+        Ok(self)
+    }
+
+    fn configure_identity(mut self, identity: Identity) -> TlsResult<SslConnectorBuilder> {
+        self.set_certificate(&identity.cert)?;
+        self.set_private_key(&identity.key)?;
+        self.check_private_key()?;
+
+        for cert in identity.chain {
+            self.add_extra_chain_cert(cert)?;
+        }
+
+        Ok(self)
+    }
+
     #[cfg(feature = "boring-tls-native-roots")]
     fn configure_set_verify_cert_store(mut self) -> TlsResult<SslConnectorBuilder> {
         use boring::x509::{store::X509StoreBuilder, X509};
@@ -245,9 +446,13 @@ impl TlsConnectExtension for ConnectConfiguration {
             return Ok(self);
         }
 
+        // Mirrors `configure_alpn_protos`: this `ConnectConfiguration` is only
+        // ever used for a TCP/TLS connection, so `Http3` negotiates the same
+        // application settings as the TCP fallback rather than advertising
+        // "h3" over a transport that can't speak it.
         let (alpn, alpn_len) = match http_version {
             HttpVersionPref::Http1 => ("http/1.1", 8),
-            HttpVersionPref::Http2 | HttpVersionPref::All => ("h2", 2),
+            HttpVersionPref::Http2 | HttpVersionPref::Http3 | HttpVersionPref::All => ("h2", 2),
         };
 
         unsafe {
@@ -261,4 +466,18 @@ impl TlsConnectExtension for ConnectConfiguration {
             .map(|_| self)
         }
     }
+
+    fn configure_ech_config_list(
+        &mut self,
+        config_list: &[u8],
+    ) -> TlsResult<&mut ConnectConfiguration> {
+        unsafe {
+            sv_handler(boring_sys::SSL_set1_ech_config_list(
+                self.as_ptr(),
+                config_list.as_ptr(),
+                config_list.len(),
+            ))
+            .map(|_| self)
+        }
+    }
 }
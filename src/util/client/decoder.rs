@@ -0,0 +1,259 @@
+#![allow(missing_debug_implementations)]
+//! Transparent, streaming response body decompression.
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use async_compression::tokio::bufread::{BrotliDecoder, GzipDecoder, ZlibDecoder, ZstdDecoder};
+use bytes::Bytes;
+use futures_util::Stream as _;
+use http::{
+    header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH},
+    HeaderMap, HeaderValue,
+};
+use http_body::{Body, Frame};
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use crate::error::BoxError;
+
+/// The content encodings a response body can be transparently decoded from.
+///
+/// Also doubles as the set of tokens that may be negotiated in the
+/// `Accept-Encoding` request header: every token this adds is one the decoder
+/// is able to unwrap, while tokens already present in the header are kept as
+/// they are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ContentEncoding {
+    Gzip,
+    Deflate,
+    Brotli,
+    Zstd,
+}
+
+impl ContentEncoding {
+    /// Returns the encoding named by a `Content-Encoding` header value, if
+    /// this decoder knows how to unwrap it.
+    pub(crate) fn from_header_value(value: &HeaderValue) -> Option<Self> {
+        match value.to_str().ok()?.trim().to_ascii_lowercase().as_str() {
+            "gzip" => Some(Self::Gzip),
+            "deflate" => Some(Self::Deflate),
+            "br" => Some(Self::Brotli),
+            "zstd" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// The token this encoding is negotiated with in `Accept-Encoding`.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Deflate => "deflate",
+            Self::Brotli => "br",
+            Self::Zstd => "zstd",
+        }
+    }
+}
+
+/// Appends the given encodings to the request's `Accept-Encoding` header,
+/// merging with whatever value is already there rather than replacing it, so
+/// a profile-supplied value (set to match the impersonated browser) keeps
+/// its existing tokens and ordering.
+pub(crate) fn append_accept_encoding(headers: &mut HeaderMap, encodings: &[ContentEncoding]) {
+    if encodings.is_empty() {
+        return;
+    }
+
+    let mut tokens = headers
+        .get(ACCEPT_ENCODING)
+        .and_then(|existing| existing.to_str().ok())
+        .map(|existing| {
+            existing
+                .split(',')
+                .map(str::trim)
+                .filter(|token| !token.is_empty())
+                .map(str::to_owned)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    for encoding in encodings {
+        let token = encoding.as_str();
+        if !tokens.iter().any(|existing| existing.eq_ignore_ascii_case(token)) {
+            tokens.push(token.to_owned());
+        }
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&tokens.join(", ")) {
+        headers.insert(ACCEPT_ENCODING, value);
+    }
+}
+
+/// A response body that transparently decodes a single content encoding as
+/// it streams, rather than buffering the whole body first.
+pub(crate) struct Decoder<B> {
+    inner: Inner<B>,
+}
+
+enum Inner<B> {
+    PlainText(B),
+    Gzip(ReaderStream<GzipDecoder<StreamReader<BodyAsStream<B>, Bytes>>>),
+    Deflate(ReaderStream<ZlibDecoder<StreamReader<BodyAsStream<B>, Bytes>>>),
+    Brotli(ReaderStream<BrotliDecoder<StreamReader<BodyAsStream<B>, Bytes>>>),
+    Zstd(ReaderStream<ZstdDecoder<StreamReader<BodyAsStream<B>, Bytes>>>),
+}
+
+impl<B> Decoder<B>
+where
+    B: Body<Data = Bytes> + Unpin,
+    B::Error: Into<BoxError>,
+{
+    /// Wraps `body` in a decoder for `encoding`, or passes it through
+    /// unchanged if no encoding (or an unsupported one) is given.
+    pub(crate) fn new(body: B, encoding: Option<ContentEncoding>) -> Self {
+        let inner = match encoding {
+            None => Inner::PlainText(body),
+            Some(ContentEncoding::Gzip) => {
+                Inner::Gzip(ReaderStream::new(GzipDecoder::new(StreamReader::new(
+                    BodyAsStream(body),
+                ))))
+            }
+            Some(ContentEncoding::Deflate) => {
+                Inner::Deflate(ReaderStream::new(ZlibDecoder::new(StreamReader::new(
+                    BodyAsStream(body),
+                ))))
+            }
+            Some(ContentEncoding::Brotli) => {
+                Inner::Brotli(ReaderStream::new(BrotliDecoder::new(StreamReader::new(
+                    BodyAsStream(body),
+                ))))
+            }
+            Some(ContentEncoding::Zstd) => {
+                Inner::Zstd(ReaderStream::new(ZstdDecoder::new(StreamReader::new(
+                    BodyAsStream(body),
+                ))))
+            }
+        };
+
+        Self { inner }
+    }
+
+    /// Strips the headers that no longer describe the decoded body: the
+    /// original `Content-Encoding` no longer applies, and `Content-Length`
+    /// described the compressed length, not the decoded one.
+    pub(crate) fn strip_headers(headers: &mut HeaderMap) {
+        headers.remove(CONTENT_ENCODING);
+        headers.remove(CONTENT_LENGTH);
+    }
+}
+
+impl<B> Body for Decoder<B>
+where
+    B: Body<Data = Bytes> + Unpin,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.get_mut();
+        match &mut this.inner {
+            Inner::PlainText(body) => match Pin::new(body).poll_frame(cx) {
+                Poll::Ready(Some(Ok(frame))) => Poll::Ready(Some(Ok(frame))),
+                Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err.into()))),
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            },
+            Inner::Gzip(stream) => poll_decoder_frame(Pin::new(stream), cx),
+            Inner::Deflate(stream) => poll_decoder_frame(Pin::new(stream), cx),
+            Inner::Brotli(stream) => poll_decoder_frame(Pin::new(stream), cx),
+            Inner::Zstd(stream) => poll_decoder_frame(Pin::new(stream), cx),
+        }
+    }
+}
+
+fn poll_decoder_frame<R>(
+    stream: Pin<&mut ReaderStream<R>>,
+    cx: &mut Context<'_>,
+) -> Poll<Option<Result<Frame<Bytes>, BoxError>>>
+where
+    R: tokio::io::AsyncRead,
+{
+    match stream.poll_next(cx) {
+        Poll::Ready(Some(Ok(chunk))) => Poll::Ready(Some(Ok(Frame::data(chunk)))),
+        Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(err.into()))),
+        Poll::Ready(None) => Poll::Ready(None),
+        Poll::Pending => Poll::Pending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_header_value_is_case_insensitive_and_trims_whitespace() {
+        let value = HeaderValue::from_static(" GZIP ");
+        assert_eq!(ContentEncoding::from_header_value(&value), Some(ContentEncoding::Gzip));
+    }
+
+    #[test]
+    fn from_header_value_rejects_unknown_tokens() {
+        let value = HeaderValue::from_static("identity");
+        assert_eq!(ContentEncoding::from_header_value(&value), None);
+    }
+
+    #[test]
+    fn append_accept_encoding_merges_with_existing_value() {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_ENCODING, HeaderValue::from_static("gzip"));
+
+        append_accept_encoding(&mut headers, &[ContentEncoding::Gzip, ContentEncoding::Brotli]);
+
+        assert_eq!(headers[ACCEPT_ENCODING], "gzip, br");
+    }
+
+    #[test]
+    fn append_accept_encoding_is_a_noop_for_an_empty_list() {
+        let mut headers = HeaderMap::new();
+        append_accept_encoding(&mut headers, &[]);
+        assert!(!headers.contains_key(ACCEPT_ENCODING));
+    }
+}
+
+/// Adapts an [`http_body::Body`] into the [`futures_util::Stream`] that
+/// [`StreamReader`] expects, so a decoder can read a response body as bytes
+/// arrive rather than after it has fully buffered.
+pub(crate) struct BodyAsStream<B>(B);
+
+impl<B> futures_util::Stream for BodyAsStream<B>
+where
+    B: Body<Data = Bytes> + Unpin,
+    B::Error: Into<BoxError>,
+{
+    type Item = std::io::Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let body = &mut self.get_mut().0;
+        loop {
+            match Pin::new(&mut *body).poll_frame(cx) {
+                Poll::Ready(Some(Ok(frame))) => match frame.into_data() {
+                    Ok(data) => return Poll::Ready(Some(Ok(data))),
+                    Err(_trailers) => continue,
+                },
+                Poll::Ready(Some(Err(err))) => {
+                    return Poll::Ready(Some(Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        err.into(),
+                    ))));
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,15 @@
+mod decoder;
+mod request;
+
+pub(crate) use decoder::{append_accept_encoding, ContentEncoding, Decoder};
+pub use request::{
+    Http2Settings, InnerRequest, InnerRequestBuilder, Priority, PseudoOrder, SettingsOrder,
+};
+
+/// The network-level configuration (interface, local address, proxy, ...) a
+/// request is sent over.
+#[derive(Clone, Debug, Default)]
+pub struct NetworkScheme {
+    // Intentionally minimal: only what the rest of `util::client` currently
+    // threads through (see `InnerRequestBuilder::network_scheme`).
+}
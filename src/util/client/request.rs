@@ -19,6 +19,7 @@ where
     request: Request<B>,
     version_pref: Option<HttpVersionPref>,
     network_scheme: NetworkScheme,
+    http2_settings: Option<Http2Settings>,
 }
 
 impl<B> InnerRequest<B>
@@ -33,6 +34,7 @@ where
             version_pref: None,
             network_scheme: Default::default(),
             headers_order: None,
+            http2_settings: None,
             _body: PhantomData,
         }
     }
@@ -40,6 +42,18 @@ where
     pub fn pieces(self) -> (Request<B>, NetworkScheme, Option<HttpVersionPref>) {
         (self.request, self.network_scheme, self.version_pref)
     }
+
+    /// The HTTP/2 SETTINGS and pseudo-header order requested for this
+    /// request, if any.
+    ///
+    /// Kept separate from [`InnerRequest::pieces`] (rather than widening its
+    /// tuple) so existing callers are unaffected. [`crate::ClientBuilder`]
+    /// does not read this value: it takes its own [`Http2Settings`] through
+    /// [`crate::ClientBuilder::http2_settings`] and applies them with
+    /// [`Http2Settings::apply`].
+    pub fn http2_settings(&self) -> Option<&Http2Settings> {
+        self.http2_settings.as_ref()
+    }
 }
 
 /// A builder for constructing HTTP requests.
@@ -53,6 +67,7 @@ where
     version_pref: Option<HttpVersionPref>,
     network_scheme: NetworkScheme,
     headers_order: Option<&'a [HeaderName]>,
+    http2_settings: Option<Http2Settings>,
     _body: PhantomData<B>,
 }
 
@@ -109,6 +124,13 @@ where
         self
     }
 
+    /// Set the HTTP/2 SETTINGS and pseudo-header order for the request.
+    #[inline]
+    pub fn http2_settings(mut self, http2_settings: Option<Http2Settings>) -> Self {
+        self.http2_settings = http2_settings;
+        self
+    }
+
     /// Set the body for the request.
     #[inline]
     pub fn body(mut self, body: B) -> InnerRequest<B> {
@@ -123,6 +145,7 @@ where
             request: self.builder.body(body).expect("failed to build request"),
             version_pref: self.version_pref,
             network_scheme: self.network_scheme,
+            http2_settings: self.http2_settings,
         }
     }
 }
@@ -131,10 +154,32 @@ fn map_version_to_pref(version: Version) -> HttpVersionPref {
     match version {
         Version::HTTP_11 | Version::HTTP_10 | Version::HTTP_09 => HttpVersionPref::Http1,
         Version::HTTP_2 => HttpVersionPref::Http2,
+        Version::HTTP_3 => HttpVersionPref::Http3,
         _ => HttpVersionPref::default(),
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_version_to_pref_matches_http2_by_default() {
+        assert_eq!(map_version_to_pref(Version::HTTP_2), HttpVersionPref::Http2);
+    }
+
+    #[test]
+    fn map_version_to_pref_matches_http3() {
+        assert_eq!(map_version_to_pref(Version::HTTP_3), HttpVersionPref::Http3);
+    }
+
+    #[test]
+    fn map_version_to_pref_treats_http1_dot_x_as_http1() {
+        assert_eq!(map_version_to_pref(Version::HTTP_11), HttpVersionPref::Http1);
+        assert_eq!(map_version_to_pref(Version::HTTP_10), HttpVersionPref::Http1);
+    }
+}
+
 fn add_content_length_header<B>(body: &B, headers: &mut HeaderMap)
 where
     B: Body,
@@ -145,3 +190,88 @@ where
             .or_insert_with(|| HeaderValue::from(len));
     }
 }
+
+/// The HTTP/2 pseudo-headers, in the order an impersonated profile emits
+/// them on the wire (`:method`, `:authority`, `:scheme`, `:path` for most
+/// browsers, though the order itself varies by client).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PseudoOrder {
+    Method,
+    Authority,
+    Scheme,
+    Path,
+}
+
+/// A single HTTP/2 SETTINGS parameter, named so an impersonation profile can
+/// also pin the order those parameters are sent in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettingsOrder {
+    HeaderTableSize,
+    EnablePush,
+    MaxConcurrentStreams,
+    InitialWindowSize,
+    MaxFrameSize,
+    MaxHeaderListSize,
+}
+
+/// A `PRIORITY` frame sent immediately after the connection preface, as some
+/// browsers do to establish a stream-dependency tree up front.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Priority {
+    pub stream_id: u32,
+    pub dependency_id: u32,
+    pub weight: u8,
+    pub exclusive: bool,
+}
+
+/// HTTP/2 connection- and stream-level settings used to reproduce a
+/// specific client's fingerprint: the SETTINGS frame (values and the order
+/// they're sent in), the WINDOW_UPDATE increment, any up-front `PRIORITY`
+/// frames, and the pseudo-header emission order.
+///
+/// This is configuration data only; applying it to the wire is the h2
+/// connection builder's job.
+#[derive(Clone, Debug, Default)]
+pub struct Http2Settings {
+    pub header_table_size: Option<u32>,
+    pub enable_push: Option<bool>,
+    pub max_concurrent_streams: Option<u32>,
+    pub initial_window_size: Option<u32>,
+    pub max_frame_size: Option<u32>,
+    pub max_header_list_size: Option<u32>,
+    pub settings_order: Option<Vec<SettingsOrder>>,
+    pub window_update_increment: Option<u32>,
+    pub priorities: Vec<Priority>,
+    pub headers_pseudo_order: Option<[PseudoOrder; 4]>,
+}
+
+impl Http2Settings {
+    /// Applies the connection-level settings to an h2 client connection
+    /// builder.
+    ///
+    /// `settings_order`, `window_update_increment`, `priorities`, and
+    /// `headers_pseudo_order` aren't exposed by the stock `h2` crate's
+    /// builder, so reproducing them on the wire needs the same kind of
+    /// patched dependency this project already relies on for `boring` in
+    /// place of `openssl`; wire those through once that fork is vendored.
+    pub fn apply(&self, builder: &mut h2::client::Builder) {
+        if let Some(header_table_size) = self.header_table_size {
+            builder.header_table_size(header_table_size);
+        }
+        if let Some(enable_push) = self.enable_push {
+            builder.enable_push(enable_push);
+        }
+        if let Some(max_concurrent_streams) = self.max_concurrent_streams {
+            builder.max_concurrent_streams(max_concurrent_streams);
+        }
+        if let Some(initial_window_size) = self.initial_window_size {
+            builder.initial_window_size(initial_window_size);
+        }
+        if let Some(max_frame_size) = self.max_frame_size {
+            builder.max_frame_size(max_frame_size);
+        }
+        if let Some(max_header_list_size) = self.max_header_list_size {
+            builder.max_header_list_size(max_header_list_size);
+        }
+    }
+}
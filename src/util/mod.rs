@@ -0,0 +1,25 @@
+//! Internal request/response plumbing used by [`crate::Client`].
+
+use http::{HeaderMap, HeaderName};
+
+pub(crate) mod client;
+
+/// Reorders `headers` so entries named in `order` come first, in that
+/// order, followed by any remaining headers in their original order.
+pub(crate) fn sort_headers(headers: &mut HeaderMap, order: &[HeaderName]) {
+    let mut sorted = HeaderMap::with_capacity(headers.len());
+
+    for name in order {
+        for value in headers.get_all(name) {
+            sorted.append(name.clone(), value.clone());
+        }
+    }
+
+    for (name, value) in headers.iter() {
+        if !order.contains(name) {
+            sorted.append(name.clone(), value.clone());
+        }
+    }
+
+    std::mem::swap(headers, &mut sorted);
+}